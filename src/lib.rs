@@ -15,20 +15,56 @@ type Key = (i32, i32);
 #[derive(Default, Reflect, Debug, Clone)]
 pub struct SparseGrid2d<const TILE_SIZE: usize = 1> {
     map: HashMap<Key, SmallVec<[Entity; 5]>>,
+    /// Reverse index of which cells each entity currently occupies, so it can be relocated or
+    /// removed without a full `clear`/reinsert pass
+    entities: HashMap<Entity, SmallVec<[Key; 5]>>,
+    /// Runtime override for the cell size, falling back to `TILE_SIZE` when unset
+    cell_size: Option<f32>,
 }
 
 impl<const TILE_SIZE: usize> SparseGrid2d<TILE_SIZE> {
+    /// Create a grid using a runtime-chosen cell size instead of the compile-time `TILE_SIZE`
+    /// generic, for when the size needs to come from config or be tuned per-world
+    pub fn with_cell_size(cell_size: f32) -> Self {
+        Self {
+            cell_size: Some(cell_size),
+            ..Default::default()
+        }
+    }
+
+    /// The cell size this grid actually uses: the runtime override if set via
+    /// [`Self::with_cell_size`], otherwise the `TILE_SIZE` generic
+    #[inline]
+    fn cell_size(&self) -> f32 {
+        self.cell_size.unwrap_or(TILE_SIZE as f32)
+    }
+
     /// Insert an entity in the given Aabb coordinates
+    ///
+    /// Idempotent per cell: inserting the same entity again over a cell it already occupies
+    /// doesn't add a second copy, so the reverse index stays a true set of occupied cells and
+    /// `remove_entity`/`move_entity_aabb` can't leave a phantom entity behind.
     pub fn insert_aabb(&mut self, aabb: impl Into<Aabb2d>, entity: Entity) {
-        for key in KeyIter::new::<TILE_SIZE>(aabb) {
-            self.map.entry(key).or_default().push(entity);
+        let s = self.cell_size();
+        let keys = self.entities.entry(entity).or_default();
+        for key in KeyIter::new(aabb, s) {
+            if !keys.contains(&key) {
+                self.map.entry(key).or_default().push(entity);
+                keys.push(key);
+            }
         }
     }
 
     /// Insert an entity at the given point coordinate
+    ///
+    /// Idempotent per cell, like [`Self::insert_aabb`].
     pub fn insert_point(&mut self, point: Vec2, entity: Entity) {
-        let key = Self::key_from_point(point);
-        self.map.entry(key).or_default().push(entity);
+        let key = self.key_from_point(point);
+        let keys = self.entities.entry(entity).or_default();
+        if !keys.contains(&key) {
+            self.map.entry(key).or_default().push(entity);
+            keys.push(key);
+        }
     }
 
     /// Get an iterator with the entities in the grid cells covered by the given [`Aabb2d`]
@@ -36,7 +72,7 @@ impl<const TILE_SIZE: usize> SparseGrid2d<TILE_SIZE> {
     /// may contain duplicates if some entities are in more than one grid cell
     #[inline]
     pub fn aabb_iter(&'_ self, aabb: impl Into<Aabb2d>) -> impl Iterator<Item = Entity> + '_ {
-        KeyIter::new::<TILE_SIZE>(aabb)
+        KeyIter::new(aabb, self.cell_size())
             .filter_map(|key| self.map.get(&key))
             .flatten()
             .copied()
@@ -45,7 +81,7 @@ impl<const TILE_SIZE: usize> SparseGrid2d<TILE_SIZE> {
     /// Get an iterator with the entities in the grid cells at the given point
     #[inline]
     pub fn point_iter(&'_ self, point: Vec2) -> impl Iterator<Item = Entity> + '_ {
-        let key = Self::key_from_point(point);
+        let key = self.key_from_point(point);
 
         std::iter::once(key)
             .filter_map(|key| self.map.get(&key))
@@ -59,9 +95,112 @@ impl<const TILE_SIZE: usize> SparseGrid2d<TILE_SIZE> {
         self.aabb_iter(aabb).collect()
     }
 
+    /// Get an iterator with the entities in the grid cells covered by the given [`Aabb2d`], with
+    /// duplicate entities suppressed
+    ///
+    /// Unlike [`Self::query_aabb`], this doesn't collect into a [`HashSet`] up front: it keeps a
+    /// small reusable buffer of entities already yielded and skips repeats as it goes, so callers
+    /// who just want correct-by-default iteration don't pay for an eager allocation.
+    #[inline]
+    pub fn aabb_iter_dedup(&'_ self, aabb: impl Into<Aabb2d>) -> AabbDedupIter<'_> {
+        AabbDedupIter {
+            keys: KeyIter::new(aabb, self.cell_size()),
+            map: &self.map,
+            current: EMPTY_BUCKET.iter(),
+            seen: SmallVec::new(),
+        }
+    }
+
+    /// Get an iterator with the entities in the grid cells overlapping the bounding box of a
+    /// circle
+    ///
+    /// Candidates only, like [`Self::aabb_iter`]: may contain duplicates, and may include
+    /// entities outside the circle itself since only the bounding box of the circle is tested
+    /// against the grid. Use [`Self::circle_iter_strict`] to cut out the corner false positives.
+    #[inline]
+    pub fn circle_iter(&'_ self, center: Vec2, radius: f32) -> impl Iterator<Item = Entity> + '_ {
+        self.aabb_iter(circle_aabb(center, radius))
+    }
+
+    /// Like [`Self::circle_iter`], but also skips grid cells whose nearest point is farther from
+    /// `center` than `radius`, giving fewer false positives at the corners of the bounding box
+    #[inline]
+    pub fn circle_iter_strict(
+        &'_ self,
+        center: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        let radius_sq = radius * radius;
+        let s = self.cell_size();
+        KeyIter::new(circle_aabb(center, radius), s)
+            .filter(move |&key| {
+                cell_nearest_point(key, s, center).distance_squared(center) <= radius_sq
+            })
+            .filter_map(|key| self.map.get(&key))
+            .flatten()
+            .copied()
+    }
+
+    /// Get an iterator that walks the grid cells a ray passes through, in near-to-far order,
+    /// yielding the entities found in each cell as it's visited
+    ///
+    /// Implements Amanatides-Woo voxel traversal, so callers can do line-of-sight / projectile
+    /// checks without scanning a fat [`Aabb2d`]. May contain duplicates, like [`Self::aabb_iter`],
+    /// if an entity spans more than one visited cell. `dir` is normalized internally, so
+    /// `max_dist` is always in world units regardless of `dir`'s length (e.g. passing
+    /// `target - origin` directly as `dir` works as expected).
+    #[inline]
+    pub fn ray_iter(
+        &'_ self,
+        origin: Vec2,
+        dir: Vec2,
+        max_dist: f32,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        RayKeyIter::new(origin, dir, max_dist, self.cell_size())
+            .filter_map(|key| self.map.get(&key))
+            .flatten()
+            .copied()
+    }
+
+    /// Remove an entity from every grid cell it currently occupies
+    ///
+    /// No-op if the entity isn't tracked by this grid. This is the cheap alternative to
+    /// `clear`/reinsert when only a handful of entities need to disappear from the grid.
+    pub fn remove_entity(&mut self, entity: Entity) {
+        let Some(keys) = self.entities.remove(&entity) else {
+            return;
+        };
+
+        for key in keys {
+            self.remove_from_cell(key, entity);
+        }
+    }
+
+    /// Move an entity to a new [`Aabb2d`], touching only the grid cells that actually changed
+    ///
+    /// Equivalent to `remove_entity` followed by `insert_aabb`, but cells shared between the old
+    /// and new position are left untouched, so for an entity that moved a short distance only the
+    /// buckets of the cells that changed are modified. The old and new cell lists are still each
+    /// walked in full to find those cells.
+    pub fn move_entity_aabb(&mut self, entity: Entity, new_aabb: impl Into<Aabb2d>) {
+        let new_keys: SmallVec<[Key; 5]> = KeyIter::new(new_aabb, self.cell_size()).collect();
+        let old_keys = self
+            .entities
+            .insert(entity, new_keys.clone())
+            .unwrap_or_default();
+
+        for &key in old_keys.iter().filter(|key| !new_keys.contains(key)) {
+            self.remove_from_cell(key, entity);
+        }
+        for &key in new_keys.iter().filter(|key| !old_keys.contains(key)) {
+            self.map.entry(key).or_default().push(entity);
+        }
+    }
+
     /// Remove all entities from the map
     pub fn clear(&mut self) {
         self.map.clear();
+        self.entities.clear();
     }
 
     /// Remove all entities from the map, but keep the heap-allocated inner data structures
@@ -69,13 +208,73 @@ impl<const TILE_SIZE: usize> SparseGrid2d<TILE_SIZE> {
         for (_, vec) in self.map.iter_mut() {
             vec.clear()
         }
+        for (_, keys) in self.entities.iter_mut() {
+            keys.clear()
+        }
     }
 
-    fn key_from_point(point: Vec2) -> Key {
-        (
-            (point.x / TILE_SIZE as f32).floor() as i32,
-            (point.y / TILE_SIZE as f32).floor() as i32,
-        )
+    fn key_from_point(&self, point: Vec2) -> Key {
+        let s = self.cell_size();
+        ((point.x / s).floor() as i32, (point.y / s).floor() as i32)
+    }
+
+    fn remove_from_cell(&mut self, key: Key, entity: Entity) {
+        if let Some(bucket) = self.map.get_mut(&key) {
+            if let Some(index) = bucket.iter().position(|&e| e == entity) {
+                bucket.swap_remove(index);
+            }
+            if bucket.is_empty() {
+                self.map.remove(&key);
+            }
+        }
+    }
+}
+
+/// The bounding [`Aabb2d`] of a circle
+fn circle_aabb(center: Vec2, radius: f32) -> Aabb2d {
+    Aabb2d {
+        min: center - Vec2::splat(radius),
+        max: center + Vec2::splat(radius),
+    }
+}
+
+/// The point in the given grid cell closest to `point`
+fn cell_nearest_point(key: Key, cell_size: f32, point: Vec2) -> Vec2 {
+    let min = Vec2::new(key.0 as f32, key.1 as f32) * cell_size;
+    let max = min + Vec2::splat(cell_size);
+    point.clamp(min, max)
+}
+
+const EMPTY_BUCKET: &[Entity] = &[];
+
+/// Iterator returned by [`SparseGrid2d::aabb_iter_dedup`]
+pub struct AabbDedupIter<'a> {
+    keys: KeyIter,
+    map: &'a HashMap<Key, SmallVec<[Entity; 5]>>,
+    current: std::slice::Iter<'a, Entity>,
+    seen: SmallVec<[Entity; 8]>,
+}
+
+impl Iterator for AabbDedupIter<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        loop {
+            if let Some(&entity) = self.current.next() {
+                if self.seen.contains(&entity) {
+                    continue;
+                }
+                self.seen.push(entity);
+                return Some(entity);
+            }
+
+            let key = self.keys.next()?;
+            self.current = self
+                .map
+                .get(&key)
+                .map_or(EMPTY_BUCKET, SmallVec::as_slice)
+                .iter();
+        }
     }
 }
 
@@ -87,10 +286,10 @@ struct KeyIter {
 }
 
 impl KeyIter {
-    fn new<const TILE_SIZE: usize>(aabb: impl Into<Aabb2d>) -> Self {
+    fn new(aabb: impl Into<Aabb2d>, cell_size: f32) -> Self {
         let Aabb2d { min, max } = aabb.into();
         // convert to key space
-        let s = TILE_SIZE as f32;
+        let s = cell_size;
         let min = ((min.x / s).floor() as i32, (min.y / s).floor() as i32);
         let max = ((max.x / s).ceil() as i32, (max.y / s).ceil() as i32);
         let width = max.0 - min.0;
@@ -122,6 +321,277 @@ impl Iterator for KeyIter {
     }
 }
 
+/// Walks the grid cells along a ray, in near-to-far order, via Amanatides-Woo voxel traversal
+struct RayKeyIter {
+    key: Key,
+    step: (i32, i32),
+    t_max: Vec2,
+    t_delta: Vec2,
+    t: f32,
+    max_dist: f32,
+}
+
+impl RayKeyIter {
+    fn new(origin: Vec2, dir: Vec2, max_dist: f32, cell_size: f32) -> Self {
+        let s = cell_size;
+        let key = ((origin.x / s).floor() as i32, (origin.y / s).floor() as i32);
+        // normalize so `t`/`max_dist` are always in world units, regardless of `dir`'s length
+        let dir = dir.normalize_or_zero();
+
+        let (step_x, t_max_x, t_delta_x) = Self::axis(origin.x, dir.x, key.0, s);
+        let (step_y, t_max_y, t_delta_y) = Self::axis(origin.y, dir.y, key.1, s);
+
+        Self {
+            key,
+            step: (step_x, step_y),
+            t_max: Vec2::new(t_max_x, t_max_y),
+            t_delta: Vec2::new(t_delta_x, t_delta_y),
+            t: 0.0,
+            max_dist,
+        }
+    }
+
+    /// Per-axis step direction, distance to the first cell boundary and the distance needed to
+    /// cross one whole cell, for a ray starting at `origin` heading in direction `dir`
+    ///
+    /// A zero `dir` component never advances, so its `t_max` is left at infinity.
+    fn axis(origin: f32, dir: f32, cell: i32, cell_size: f32) -> (i32, f32, f32) {
+        if dir > 0.0 {
+            let next_boundary = (cell + 1) as f32 * cell_size;
+            (1, (next_boundary - origin) / dir, cell_size / dir)
+        } else if dir < 0.0 {
+            let next_boundary = cell as f32 * cell_size;
+            (-1, (next_boundary - origin) / dir, cell_size / -dir)
+        } else {
+            (0, f32::INFINITY, f32::INFINITY)
+        }
+    }
+}
+
+impl Iterator for RayKeyIter {
+    type Item = Key;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.t > self.max_dist {
+            return None;
+        }
+
+        let current = self.key;
+
+        if self.t_max.x < self.t_max.y {
+            self.t = self.t_max.x;
+            self.t_max.x += self.t_delta.x;
+            self.key.0 += self.step.0;
+        } else {
+            self.t = self.t_max.y;
+            self.t_max.y += self.t_delta.y;
+            self.key.1 += self.step.1;
+        }
+
+        Some(current)
+    }
+}
+
+/// Alternative to [`SparseGrid2d`] for worlds with many entities per cell
+///
+/// Each cell owns a contiguous `(start, len)` block of one shared `entities` arena, so
+/// `aabb_iter` walks a plain slice per overlapped cell instead of chasing per-entity pointers.
+/// Growing a cell that isn't already at the end of the arena relocates just that cell's own
+/// block to the tail first (cost proportional to that cell's occupancy, not the whole grid);
+/// the vacated slots, and the ones `remove_entity` swap-removes from within a block, are left as
+/// unreachable garbage rather than patched up immediately, which is what keeps individual inserts
+/// and removals cheap. [`Self::compact`] reclaims all of that garbage in one pass.
+#[derive(Default, Reflect, Debug, Clone)]
+pub struct SparseGrid2dDense<const TILE_SIZE: usize = 1> {
+    /// Entities packed into per-cell contiguous blocks; slots not covered by any entry in `cells`
+    /// are garbage left behind by insertion relocating a block or removal shrinking one
+    entities: Vec<Entity>,
+    /// Cell -> the `(start, len)` block of `entities` it owns
+    cells: HashMap<Key, (u32, u32)>,
+    /// Which (key, arena index) pairs each entity currently occupies
+    locations: HashMap<Entity, SmallVec<[(Key, u32); 5]>>,
+    cell_size: Option<f32>,
+}
+
+impl<const TILE_SIZE: usize> SparseGrid2dDense<TILE_SIZE> {
+    /// Create a grid using a runtime-chosen cell size instead of the compile-time `TILE_SIZE`
+    /// generic, same as [`SparseGrid2d::with_cell_size`]
+    pub fn with_cell_size(cell_size: f32) -> Self {
+        Self {
+            cell_size: Some(cell_size),
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    fn cell_size(&self) -> f32 {
+        self.cell_size.unwrap_or(TILE_SIZE as f32)
+    }
+
+    /// Insert an entity in the given Aabb coordinates
+    ///
+    /// Inserting the same entity into a cell it already occupies is a no-op, same as
+    /// [`SparseGrid2d::insert_aabb`].
+    pub fn insert_aabb(&mut self, aabb: impl Into<Aabb2d>, entity: Entity) {
+        let s = self.cell_size();
+        for key in KeyIter::new(aabb, s) {
+            self.insert_into_cell(key, entity);
+        }
+    }
+
+    /// Insert an entity at the given point coordinate
+    ///
+    /// Inserting the same entity at a point in a cell it already occupies is a no-op, same as
+    /// [`SparseGrid2d::insert_point`].
+    pub fn insert_point(&mut self, point: Vec2, entity: Entity) {
+        let key = self.key_from_point(point);
+        self.insert_into_cell(key, entity);
+    }
+
+    fn insert_into_cell(&mut self, key: Key, entity: Entity) {
+        if self
+            .locations
+            .get(&entity)
+            .is_some_and(|locations| locations.iter().any(|&(k, _)| k == key))
+        {
+            return;
+        }
+
+        let (start, len) = match self.cells.get(&key) {
+            Some(&block) => block,
+            None => (self.entities.len() as u32, 0),
+        };
+
+        // grow in place if this cell's block is already at the tail, otherwise relocate the
+        // whole block there first so it has room to grow contiguously
+        let start = if (start + len) as usize == self.entities.len() {
+            start
+        } else {
+            let new_start = self.entities.len() as u32;
+            let block: SmallVec<[Entity; 8]> =
+                self.entities[start as usize..(start + len) as usize].into();
+            self.entities.extend_from_slice(&block);
+            for (offset, &moved) in block.iter().enumerate() {
+                self.relocate(moved, start + offset as u32, new_start + offset as u32);
+            }
+            new_start
+        };
+
+        self.entities.push(entity);
+        self.cells.insert(key, (start, len + 1));
+        self.locations
+            .entry(entity)
+            .or_default()
+            .push((key, start + len));
+    }
+
+    /// Update the (key, index) location entry for `entity`'s occurrence at `old_index` to
+    /// `new_index`, following it after it's been physically moved within `entities`
+    fn relocate(&mut self, entity: Entity, old_index: u32, new_index: u32) {
+        if let Some(locations) = self.locations.get_mut(&entity) {
+            if let Some(entry) = locations.iter_mut().find(|(_, index)| *index == old_index) {
+                entry.1 = new_index;
+            }
+        }
+    }
+
+    /// Get an iterator with the entities in the grid cells covered by the given [`Aabb2d`]
+    ///
+    /// may contain duplicates if some entities are in more than one grid cell
+    #[inline]
+    pub fn aabb_iter(&'_ self, aabb: impl Into<Aabb2d>) -> impl Iterator<Item = Entity> + '_ {
+        KeyIter::new(aabb, self.cell_size())
+            .filter_map(|key| self.cells.get(&key))
+            .flat_map(|&(start, len)| self.entities[start as usize..(start + len) as usize].iter())
+            .copied()
+    }
+
+    /// Get an iterator with the entities in the grid cells at the given point
+    #[inline]
+    pub fn point_iter(&'_ self, point: Vec2) -> impl Iterator<Item = Entity> + '_ {
+        let key = self.key_from_point(point);
+
+        self.cells
+            .get(&key)
+            .into_iter()
+            .flat_map(|&(start, len)| self.entities[start as usize..(start + len) as usize].iter())
+            .copied()
+    }
+
+    /// Remove an entity from every grid cell it currently occupies
+    pub fn remove_entity(&mut self, entity: Entity) {
+        let Some(locations) = self.locations.remove(&entity) else {
+            return;
+        };
+
+        for (key, index) in locations {
+            self.remove_from_cell(key, index);
+        }
+    }
+
+    fn remove_from_cell(&mut self, key: Key, index: u32) {
+        let Some(&(start, len)) = self.cells.get(&key) else {
+            return;
+        };
+        let last = start + len - 1;
+
+        if index != last {
+            let moved = self.entities[last as usize];
+            self.entities[index as usize] = moved;
+            self.relocate(moved, last, index);
+        }
+
+        if len > 1 {
+            self.cells.insert(key, (start, len - 1));
+        } else {
+            self.cells.remove(&key);
+        }
+    }
+
+    /// Remove all entities from the map
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.cells.clear();
+        self.locations.clear();
+    }
+
+    /// Rebuild the arena so every cell's block sits back-to-back with no gaps between them
+    ///
+    /// Insertion relocating a growing block, and removal swapping an entity out of one, both
+    /// leave a few arena slots behind as garbage to keep those operations cheap; this reclaims
+    /// all of it in one full pass, unlike a scheme that can only pop trailing holes.
+    pub fn compact(&mut self) {
+        let mut entities = Vec::with_capacity(self.entities.len());
+        let mut cells = HashMap::default();
+        cells.reserve(self.cells.len());
+
+        for (&key, &(start, len)) in &self.cells {
+            let new_start = entities.len() as u32;
+            entities.extend_from_slice(&self.entities[start as usize..(start + len) as usize]);
+            cells.insert(key, (new_start, len));
+        }
+
+        self.locations.clear();
+        for (&key, &(start, len)) in &cells {
+            for offset in 0..len {
+                let index = start + offset;
+                self.locations
+                    .entry(entities[index as usize])
+                    .or_default()
+                    .push((key, index));
+            }
+        }
+
+        self.entities = entities;
+        self.cells = cells;
+    }
+
+    fn key_from_point(&self, point: Vec2) -> Key {
+        let s = self.cell_size();
+        ((point.x / s).floor() as i32, (point.y / s).floor() as i32)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bevy::math::{bounding::Aabb2d, vec2};
@@ -133,10 +603,13 @@ mod tests {
 
     #[test]
     fn keys_single() {
-        let keys: Vec<Key> = KeyIter::new::<TILE_SIZE>(Aabb2d {
-            min: vec2(0.001, 0.001),
-            max: vec2(0.001, 0.001),
-        })
+        let keys: Vec<Key> = KeyIter::new(
+            Aabb2d {
+                min: vec2(0.001, 0.001),
+                max: vec2(0.001, 0.001),
+            },
+            TILE_SIZE as f32,
+        )
         .collect();
         assert_eq!(keys.len(), 1);
         assert_eq!(keys[0], (0, 0));
@@ -144,10 +617,13 @@ mod tests {
 
     #[test]
     fn keys_four_around_origin() {
-        let keys: Vec<Key> = KeyIter::new::<TILE_SIZE>(Aabb2d {
-            min: vec2(-0.001, -0.001),
-            max: vec2(0.001, 0.001),
-        })
+        let keys: Vec<Key> = KeyIter::new(
+            Aabb2d {
+                min: vec2(-0.001, -0.001),
+                max: vec2(0.001, 0.001),
+            },
+            TILE_SIZE as f32,
+        )
         .collect();
         assert!(keys.contains(&(0, 0)));
         assert!(keys.contains(&(0, -1)));
@@ -181,10 +657,13 @@ mod tests {
     #[test]
     fn key_negative() {
         let h = TILE_SIZE as f32 / 2.0;
-        let keys: Vec<Key> = KeyIter::new::<TILE_SIZE>(Aabb2d {
-            min: vec2(-h, -h),
-            max: vec2(-h, -h),
-        })
+        let keys: Vec<Key> = KeyIter::new(
+            Aabb2d {
+                min: vec2(-h, -h),
+                max: vec2(-h, -h),
+            },
+            TILE_SIZE as f32,
+        )
         .collect();
         assert!(keys.contains(&(-1, -1)));
         assert_eq!(keys.len(), 1);
@@ -290,4 +769,286 @@ mod tests {
         assert!(!matches.contains(&e3));
         assert_eq!(matches.len(), 2);
     }
+
+    #[test]
+    fn aabb_iter_dedup_suppresses_repeats_across_cells() {
+        let h = TILE_SIZE as f32 / 2.0;
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        db.insert_aabb(
+            Aabb2d {
+                min: vec2(-h, -h),
+                max: vec2(h, h),
+            },
+            e1,
+        );
+
+        let matches: Vec<Entity> = db
+            .aabb_iter_dedup(Aabb2d {
+                min: vec2(-h, -h),
+                max: vec2(h, h),
+            })
+            .collect();
+        assert_eq!(matches, vec![e1]);
+    }
+
+    #[test]
+    fn with_cell_size_overrides_the_tile_size_generic() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::with_cell_size(10.0);
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        let e2 = Entity::from_raw_u32(2).unwrap();
+        db.insert_point(vec2(12.0, 15.0), e1);
+        db.insert_point(vec2(15.0, 12.0), e2);
+
+        let matches: HashSet<_> = db.point_iter(vec2(19.9, 19.9)).collect();
+        assert!(matches.contains(&e1));
+        assert!(matches.contains(&e2));
+        assert_eq!(matches.len(), 2);
+    }
+
+    #[test]
+    fn remove_entity_clears_all_occupied_cells() {
+        let h = TILE_SIZE as f32 / 2.0;
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        db.insert_aabb(
+            Aabb2d {
+                min: vec2(-h, -h),
+                max: vec2(h, h),
+            },
+            e1,
+        );
+
+        db.remove_entity(e1);
+
+        let matches: Vec<Entity> = db
+            .aabb_iter(Aabb2d {
+                min: vec2(-h, -h),
+                max: vec2(h, h),
+            })
+            .collect();
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn remove_entity_is_noop_for_unknown_entity() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        db.remove_entity(e1);
+    }
+
+    #[test]
+    fn circle_iter_finds_entity_under_center() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        db.insert_point(vec2(0.5, 0.5), e1);
+
+        let matches: Vec<Entity> = db.circle_iter(vec2(0.5, 0.5), 0.1).collect();
+        assert_eq!(matches, vec![e1]);
+    }
+
+    #[test]
+    fn circle_iter_strict_excludes_far_corner_cell() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        // in the cell diagonally adjacent to the origin cell, outside the radius from it
+        db.insert_point(vec2(1.9, 1.9), e1);
+
+        let loose: Vec<Entity> = db.circle_iter(vec2(0.0, 0.0), 1.2).collect();
+        assert_eq!(loose, vec![e1]);
+
+        let strict: Vec<Entity> = db.circle_iter_strict(vec2(0.0, 0.0), 1.2).collect();
+        assert!(strict.is_empty());
+    }
+
+    #[test]
+    fn ray_iter_visits_cells_in_order_along_the_axis() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        let e2 = Entity::from_raw_u32(2).unwrap();
+        db.insert_point(vec2(2.5, 0.5), e1);
+        db.insert_point(vec2(0.5, 0.5), e2);
+
+        let matches: Vec<Entity> = db.ray_iter(vec2(0.5, 0.5), vec2(1.0, 0.0), 10.0).collect();
+        assert_eq!(matches, vec![e2, e1]);
+    }
+
+    #[test]
+    fn ray_iter_stops_at_max_dist() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        db.insert_point(vec2(5.5, 0.5), e1);
+
+        let matches: Vec<Entity> = db.ray_iter(vec2(0.5, 0.5), vec2(1.0, 0.0), 2.0).collect();
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn ray_iter_max_dist_is_world_distance_even_for_unnormalized_dir() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e_near = Entity::from_raw_u32(1).unwrap();
+        let e_far = Entity::from_raw_u32(2).unwrap();
+        db.insert_point(vec2(1.5, 0.5), e_near);
+        db.insert_point(vec2(5.5, 0.5), e_far);
+
+        let origin = vec2(0.5, 0.5);
+        let target = vec2(5.5, 0.5);
+        // `target - origin` is not unit length; max_dist must still be world units
+        let matches: Vec<Entity> = db.ray_iter(origin, target - origin, 2.0).collect();
+        assert_eq!(matches, vec![e_near]);
+    }
+
+    #[test]
+    fn ray_iter_handles_axis_aligned_ray() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        db.insert_point(vec2(0.5, 3.5), e1);
+
+        let matches: Vec<Entity> = db.ray_iter(vec2(0.5, 0.5), vec2(0.0, 1.0), 10.0).collect();
+        assert_eq!(matches, vec![e1]);
+    }
+
+    #[test]
+    fn move_entity_aabb_only_touches_changed_cells() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        db.insert_point(vec2(0.5, 0.5), e1);
+
+        db.move_entity_aabb(
+            e1,
+            Aabb2d {
+                min: vec2(10.5, 10.5),
+                max: vec2(10.5, 10.5),
+            },
+        );
+
+        assert!(db.point_iter(vec2(0.5, 0.5)).next().is_none());
+        let matches: Vec<Entity> = db.point_iter(vec2(10.5, 10.5)).collect();
+        assert_eq!(matches, vec![e1]);
+    }
+
+    #[test]
+    fn move_entity_aabb_does_not_leave_phantoms_after_overlapping_inserts() {
+        let mut db = SparseGrid2d::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        let box_ab = Aabb2d {
+            min: vec2(0.5, 0.5),
+            max: vec2(1.5, 0.5),
+        };
+        // insert the same entity over the same overlapping cells twice
+        db.insert_aabb(box_ab, e1);
+        db.insert_aabb(box_ab, e1);
+
+        // move to a cell that was already occupied, dropping the other one
+        db.move_entity_aabb(
+            e1,
+            Aabb2d {
+                min: vec2(0.5, 0.5),
+                max: vec2(0.5, 0.5),
+            },
+        );
+        db.remove_entity(e1);
+
+        assert!(db.point_iter(vec2(0.5, 0.5)).next().is_none());
+        assert!(db.point_iter(vec2(1.5, 0.5)).next().is_none());
+    }
+
+    #[test]
+    fn dense_query_points() {
+        let mut db = SparseGrid2dDense::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        let e2 = Entity::from_raw_u32(2).unwrap();
+        db.insert_point(vec2(0.5, 0.5), e1);
+        db.insert_point(vec2(0.499, 0.501), e2);
+
+        let matches: HashSet<_> = db.point_iter(vec2(0.499, 0.501)).collect();
+        assert!(matches.contains(&e1));
+        assert!(matches.contains(&e2));
+        assert_eq!(matches.len(), 2);
+    }
+
+    #[test]
+    fn dense_remove_entity_clears_all_occupied_cells() {
+        let h = TILE_SIZE as f32 / 2.0;
+        let mut db = SparseGrid2dDense::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        db.insert_aabb(
+            Aabb2d {
+                min: vec2(-h, -h),
+                max: vec2(h, h),
+            },
+            e1,
+        );
+
+        db.remove_entity(e1);
+
+        let matches: Vec<Entity> = db
+            .aabb_iter(Aabb2d {
+                min: vec2(-h, -h),
+                max: vec2(h, h),
+            })
+            .collect();
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn dense_compact_reclaims_interior_and_trailing_garbage() {
+        let mut db = SparseGrid2dDense::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        let e2 = Entity::from_raw_u32(2).unwrap();
+        let e3 = Entity::from_raw_u32(3).unwrap();
+        db.insert_point(vec2(0.5, 0.5), e1);
+        db.insert_point(vec2(10.5, 10.5), e2);
+        db.insert_point(vec2(20.5, 20.5), e3);
+
+        // removing e2 (from the middle block) leaves interior garbage, not just a trailing hole
+        db.remove_entity(e2);
+        db.compact();
+
+        assert_eq!(db.entities.len(), 2);
+        let matches: Vec<Entity> = db.point_iter(vec2(0.5, 0.5)).collect();
+        assert_eq!(matches, vec![e1]);
+        let matches: Vec<Entity> = db.point_iter(vec2(20.5, 20.5)).collect();
+        assert_eq!(matches, vec![e3]);
+    }
+
+    #[test]
+    fn dense_insert_is_idempotent_for_the_same_entity_and_cell() {
+        let h = TILE_SIZE as f32 / 2.0;
+        let mut db = SparseGrid2dDense::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        let aabb = Aabb2d {
+            min: vec2(-h, -h),
+            max: vec2(h, h),
+        };
+
+        db.insert_aabb(aabb, e1);
+        db.insert_aabb(aabb, e1);
+        db.insert_point(vec2(0.0, 0.0), e1);
+
+        // one copy per covered cell (the box spans 4 cells), not one per insert call
+        let matches: Vec<Entity> = db.aabb_iter(aabb).collect();
+        assert_eq!(matches, vec![e1; 4]);
+
+        db.remove_entity(e1);
+        assert!(db.aabb_iter(aabb).next().is_none());
+    }
+
+    #[test]
+    fn dense_aabb_iter_scans_a_contiguous_block_per_cell() {
+        let mut db = SparseGrid2dDense::<TILE_SIZE>::default();
+        let e1 = Entity::from_raw_u32(1).unwrap();
+        let e2 = Entity::from_raw_u32(2).unwrap();
+        let e3 = Entity::from_raw_u32(3).unwrap();
+        // start this cell, insert into another cell so this block is no longer at the tail, then
+        // grow this one, forcing a block relocation
+        db.insert_point(vec2(0.5, 0.5), e1);
+        db.insert_point(vec2(10.5, 10.5), e3);
+        db.insert_point(vec2(0.5, 0.5), e2);
+
+        let &(start, len) = db.cells.get(&(0, 0)).unwrap();
+        assert_eq!(len, 2);
+        let block = &db.entities[start as usize..(start + len) as usize];
+        assert_eq!(HashSet::from_iter(block.iter().copied()), HashSet::from([e1, e2]));
+    }
 }